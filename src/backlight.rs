@@ -0,0 +1,99 @@
+//! Backlight control for the LCD panel, driven by an LEDC PWM channel.
+//!
+//! LED brightness is perceived non-linearly, so percentages are pushed through
+//! a gamma-style curve before being written to the duty register. Without this
+//! the low end of the slider (the range where dimming actually matters) would
+//! be crowded into a handful of duty-cycle steps.
+
+use esp_idf_hal::ledc::LedcDriver;
+
+/// Perceptual exponent used to convert a linear percentage into a duty cycle.
+/// 2.8 approximates the CIE 1931 lightness curve commonly used for LED PWM.
+const GAMMA: f32 = 2.8;
+
+/// Discrete backlight steps, for callers that want presets rather than a
+/// continuous percentage (e.g. a long-press "cycle brightness" button).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Off,
+    Low,
+    Medium,
+    High,
+    Full,
+}
+
+impl Level {
+    fn as_percent(self) -> u8 {
+        match self {
+            Level::Off => 0,
+            Level::Low => 25,
+            Level::Medium => 50,
+            Level::High => 75,
+            Level::Full => 100,
+        }
+    }
+}
+
+/// Wraps the `LedcDriver` PWM channel that drives the panel backlight.
+pub struct Backlight<'d> {
+    channel: LedcDriver<'d>,
+}
+
+impl<'d> Backlight<'d> {
+    pub fn new(channel: LedcDriver<'d>) -> Self {
+        Self { channel }
+    }
+
+    /// Drive the backlight to one of the discrete [`Level`] steps.
+    pub fn set_level(&mut self, level: Level) -> anyhow::Result<()> {
+        self.set_percent(level.as_percent() as u32)
+    }
+
+    /// Set the backlight brightness to `percent` (0..=100), gamma-correcting
+    /// the value before scaling it to the channel's max duty.
+    pub fn set_percent(&mut self, percent: u32) -> anyhow::Result<()> {
+        let percent = percent.min(100);
+        let max_duty = self.channel.get_max_duty();
+        self.channel.set_duty(Self::gamma_correct(percent, max_duty))?;
+        Ok(())
+    }
+
+    /// Map a linear `0..=100` percentage onto a perceptual brightness curve
+    /// and scale it to `max_duty`.
+    fn gamma_correct(percent: u32, max_duty: u32) -> u32 {
+        let normalized = percent as f32 / 100.0;
+        let corrected = normalized.powf(GAMMA);
+        (corrected * max_duty as f32).round() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_correct_clamps_to_the_endpoints() {
+        assert_eq!(Backlight::gamma_correct(0, 8191), 0);
+        assert_eq!(Backlight::gamma_correct(100, 8191), 8191);
+    }
+
+    #[test]
+    fn gamma_correct_is_monotonically_increasing() {
+        let max_duty = 8191;
+        let mut previous = 0;
+        for percent in 0..=100 {
+            let duty = Backlight::gamma_correct(percent, max_duty);
+            assert!(duty >= previous, "duty dipped at {percent}%");
+            previous = duty;
+        }
+    }
+
+    #[test]
+    fn gamma_correct_dims_the_low_end_below_linear() {
+        // The whole point of the curve: at half brightness the duty cycle
+        // should be well under half of max_duty, not a linear 50%.
+        let max_duty = 8191;
+        let duty = Backlight::gamma_correct(50, max_duty);
+        assert!(duty < max_duty / 2);
+    }
+}
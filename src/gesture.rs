@@ -0,0 +1,177 @@
+//! Swipe/tap/long-press gesture recognition built on top of the GT911's
+//! multi-touch point reports. Tracks the first contact's displacement and
+//! elapsed time between polls and classifies it against simple thresholds,
+//! the same approach CST816-style touch controllers use in hardware.
+
+use std::time::{Duration, Instant};
+
+use crate::gt911::TouchPoint;
+
+const SWIPE_MIN_DISTANCE: i32 = 40;
+const SWIPE_MAX_DURATION: Duration = Duration::from_millis(400);
+const LONG_PRESS_MIN_DURATION: Duration = Duration::from_millis(500);
+const TAP_MAX_DURATION: Duration = Duration::from_millis(200);
+const TAP_MAX_MOVEMENT: i32 = 10;
+
+/// A recognized touch gesture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    SwipeUp,
+    SwipeDown,
+    SwipeLeft,
+    SwipeRight,
+    LongPress,
+    Tap,
+}
+
+struct ActiveTouch {
+    start: (i32, i32),
+    last: (i32, i32),
+    started_at: Instant,
+    long_press_fired: bool,
+}
+
+/// Tracks a single touch point across polls and emits a [`Gesture`] when its
+/// displacement/duration crosses one of the thresholds above.
+#[derive(Default)]
+pub struct GestureRecognizer {
+    active: Option<ActiveTouch>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the latest poll's points (empty once the finger lifts) and get
+    /// back any gesture that fired as a result. Only the first contact is
+    /// tracked; additional simultaneous contacts are ignored.
+    pub fn update(&mut self, points: &[TouchPoint]) -> Option<Gesture> {
+        match points.first() {
+            Some(point) => self.on_touch(point.x as i32, point.y as i32),
+            None => self.on_release(),
+        }
+    }
+
+    fn on_touch(&mut self, x: i32, y: i32) -> Option<Gesture> {
+        match &mut self.active {
+            Some(touch) => {
+                touch.last = (x, y);
+                if !touch.long_press_fired
+                    && touch.started_at.elapsed() >= LONG_PRESS_MIN_DURATION
+                    && Self::distance(touch.start, touch.last) <= TAP_MAX_MOVEMENT
+                {
+                    touch.long_press_fired = true;
+                    return Some(Gesture::LongPress);
+                }
+                None
+            }
+            None => {
+                self.active = Some(ActiveTouch {
+                    start: (x, y),
+                    last: (x, y),
+                    started_at: Instant::now(),
+                    long_press_fired: false,
+                });
+                None
+            }
+        }
+    }
+
+    fn on_release(&mut self) -> Option<Gesture> {
+        let touch = self.active.take()?;
+        if touch.long_press_fired {
+            return None;
+        }
+
+        let elapsed = touch.started_at.elapsed();
+        let distance = Self::distance(touch.start, touch.last);
+
+        if elapsed <= TAP_MAX_DURATION && distance <= TAP_MAX_MOVEMENT {
+            return Some(Gesture::Tap);
+        }
+
+        if elapsed <= SWIPE_MAX_DURATION && distance >= SWIPE_MIN_DISTANCE {
+            let dx = touch.last.0 - touch.start.0;
+            let dy = touch.last.1 - touch.start.1;
+            return Some(if dx.abs() >= dy.abs() {
+                if dx > 0 {
+                    Gesture::SwipeRight
+                } else {
+                    Gesture::SwipeLeft
+                }
+            } else if dy > 0 {
+                Gesture::SwipeDown
+            } else {
+                Gesture::SwipeUp
+            });
+        }
+
+        None
+    }
+
+    fn distance((x1, y1): (i32, i32), (x2, y2): (i32, i32)) -> i32 {
+        let dx = (x2 - x1) as f32;
+        let dy = (y2 - y1) as f32;
+        (dx * dx + dy * dy).sqrt() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: u16, y: u16) -> TouchPoint {
+        TouchPoint {
+            track_id: 0,
+            x,
+            y,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn quick_touch_and_release_without_movement_is_a_tap() {
+        let mut recognizer = GestureRecognizer::new();
+        assert_eq!(recognizer.update(&[point(100, 100)]), None);
+        assert_eq!(recognizer.update(&[]), Some(Gesture::Tap));
+    }
+
+    #[test]
+    fn fast_large_displacement_is_a_swipe() {
+        let mut recognizer = GestureRecognizer::new();
+        assert_eq!(recognizer.update(&[point(0, 100)]), None);
+        assert_eq!(recognizer.update(&[point(100, 100)]), None);
+        assert_eq!(recognizer.update(&[]), Some(Gesture::SwipeRight));
+    }
+
+    #[test]
+    fn swipe_direction_follows_the_dominant_axis() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.update(&[point(100, 0)]);
+        recognizer.update(&[point(100, 100)]);
+        assert_eq!(recognizer.update(&[]), Some(Gesture::SwipeDown));
+    }
+
+    #[test]
+    fn held_touch_past_the_threshold_fires_a_long_press() {
+        let mut recognizer = GestureRecognizer::new();
+        assert_eq!(recognizer.update(&[point(100, 100)]), None);
+        std::thread::sleep(LONG_PRESS_MIN_DURATION + Duration::from_millis(50));
+        assert_eq!(
+            recognizer.update(&[point(100, 100)]),
+            Some(Gesture::LongPress)
+        );
+        // A long press doesn't also emit a gesture on release.
+        assert_eq!(recognizer.update(&[]), None);
+    }
+
+    #[test]
+    fn slow_small_movement_is_neither_tap_nor_swipe() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.update(&[point(100, 100)]);
+        recognizer.update(&[point(105, 100)]);
+        std::thread::sleep(TAP_MAX_DURATION + Duration::from_millis(50));
+        assert_eq!(recognizer.update(&[]), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Driver for the Goodix GT911 capacitive touch controller, as wired up on
+//! the ESP32-S3 panels this crate targets (I2C + a dedicated reset line).
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+use esp_idf_hal::i2c::I2cDriver;
+
+use crate::lcd_panel::Rotation;
+
+const GT911_I2C_ADDRESS: u8 = 0x5D;
+const REG_STATUS: u16 = 0x8140;
+const REG_POINT1: u16 = 0x8150;
+const REG_POINT_STRIDE: u16 = 8;
+const MAX_TOUCH_POINTS: usize = 5;
+
+/// One contact out of the up to 5 the GT911 can report simultaneously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchPoint {
+    /// The controller's per-contact tracking ID, stable across polls while
+    /// the finger stays down.
+    pub track_id: u8,
+    pub x: u16,
+    pub y: u16,
+    pub size: u16,
+}
+
+/// Apply a rotation, then the per-axis flags, to a raw `(x, y)` reading taken
+/// against a panel of native size `width` x `height`. Pulled out of `GT911`
+/// as a free function since it's pure coordinate math with no I2C/GPIO
+/// dependency, which keeps it straightforward to unit test.
+#[allow(clippy::too_many_arguments)]
+fn remap_point(
+    width: u16,
+    height: u16,
+    rotation: Rotation,
+    swap_xy: bool,
+    mirror_x: bool,
+    mirror_y: bool,
+    x: u16,
+    y: u16,
+) -> (u16, u16) {
+    let (w, h) = (width, height);
+    let (mut x, mut y) = match rotation {
+        Rotation::Deg0 => (x, y),
+        Rotation::Deg90 => (y, w.saturating_sub(1).saturating_sub(x)),
+        Rotation::Deg180 => (
+            w.saturating_sub(1).saturating_sub(x),
+            h.saturating_sub(1).saturating_sub(y),
+        ),
+        Rotation::Deg270 => (h.saturating_sub(1).saturating_sub(y), x),
+    };
+
+    let (rot_w, rot_h) = match rotation {
+        Rotation::Deg0 | Rotation::Deg180 => (w, h),
+        Rotation::Deg90 | Rotation::Deg270 => (h, w),
+    };
+
+    if swap_xy {
+        std::mem::swap(&mut x, &mut y);
+    }
+    if mirror_x {
+        x = rot_w.saturating_sub(1).saturating_sub(x);
+    }
+    if mirror_y {
+        y = rot_h.saturating_sub(1).saturating_sub(y);
+    }
+
+    (x, y)
+}
+
+/// Driver for the GT911 touch controller.
+///
+/// `rst` is the controller's reset line; `delay` provides the reset timing.
+/// Raw touch points are transformed by `rotation` (and, after that, by the
+/// `swap_xy`/`mirror_x`/`mirror_y` axis flags) before being handed back, so
+/// callers always see coordinates in the same space LVGL is drawing to.
+pub struct GT911<'d, RST, DELAY> {
+    i2c: I2cDriver<'d>,
+    rst: RST,
+    delay: DELAY,
+    width: u16,
+    height: u16,
+    rotation: Rotation,
+    swap_xy: bool,
+    mirror_x: bool,
+    mirror_y: bool,
+}
+
+impl<'d, RST, DELAY> GT911<'d, RST, DELAY>
+where
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Create a driver for a panel of native size `width` x `height` (i.e.
+    /// the panel's size before any rotation is applied).
+    pub fn new(i2c: I2cDriver<'d>, rst: RST, delay: DELAY, width: u16, height: u16) -> Self {
+        Self {
+            i2c,
+            rst,
+            delay,
+            width,
+            height,
+            rotation: Rotation::default(),
+            swap_xy: false,
+            mirror_x: false,
+            mirror_y: false,
+        }
+    }
+
+    /// Set the rotation to apply to raw touch points. Must match the
+    /// rotation the `LcdPanel` is drawing in.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    /// Independent axis flags applied after the rotation matrix, for boards
+    /// that wire the touch controller's axes inverted relative to the panel.
+    pub fn set_axis_flags(&mut self, swap_xy: bool, mirror_x: bool, mirror_y: bool) {
+        self.swap_xy = swap_xy;
+        self.mirror_x = mirror_x;
+        self.mirror_y = mirror_y;
+    }
+
+    /// Toggle the GT911's reset line to bring the controller out of reset.
+    pub fn reset(&mut self) -> anyhow::Result<()> {
+        self.rst.set_low().ok();
+        self.delay.delay_ms(10);
+        self.rst.set_high().ok();
+        self.delay.delay_ms(100);
+        Ok(())
+    }
+
+    fn read_reg(&mut self, reg: u16, buf: &mut [u8]) -> anyhow::Result<()> {
+        let reg_bytes = reg.to_be_bytes();
+        self.i2c
+            .write_read(GT911_I2C_ADDRESS, &reg_bytes, buf, 100)
+            .map_err(anyhow::Error::from)
+    }
+
+    fn write_reg(&mut self, reg: u16, value: u8) -> anyhow::Result<()> {
+        let reg_bytes = reg.to_be_bytes();
+        self.i2c
+            .write(GT911_I2C_ADDRESS, &[reg_bytes[0], reg_bytes[1], value], 100)
+            .map_err(anyhow::Error::from)
+    }
+
+    /// Apply `self.rotation` and then the per-axis flags to a raw `(x, y)`
+    /// reading, mapping it into the same coordinate space LVGL is drawing to.
+    fn remap(&self, x: u16, y: u16) -> (u16, u16) {
+        remap_point(
+            self.width,
+            self.height,
+            self.rotation,
+            self.swap_xy,
+            self.mirror_x,
+            self.mirror_y,
+            x,
+            y,
+        )
+    }
+
+    /// Poll the controller for every contact currently down (up to 5), as
+    /// reported by the status register's touch-count nibble.
+    pub fn read_touches(&mut self) -> anyhow::Result<heapless::Vec<TouchPoint, MAX_TOUCH_POINTS>> {
+        let mut status = [0u8; 1];
+        self.read_reg(REG_STATUS, &mut status)?;
+
+        let mut points = heapless::Vec::new();
+
+        if status[0] & 0x80 == 0 {
+            return Ok(points);
+        }
+
+        let touch_count = (status[0] & 0x0F) as usize;
+
+        for i in 0..touch_count.min(MAX_TOUCH_POINTS) {
+            let mut record = [0u8; 7];
+            self.read_reg(REG_POINT1 + i as u16 * REG_POINT_STRIDE, &mut record)?;
+
+            let raw_x = u16::from_le_bytes([record[1], record[2]]);
+            let raw_y = u16::from_le_bytes([record[3], record[4]]);
+            let (x, y) = self.remap(raw_x, raw_y);
+
+            let _ = points.push(TouchPoint {
+                track_id: record[0],
+                x,
+                y,
+                size: u16::from_le_bytes([record[5], record[6]]),
+            });
+        }
+
+        // Only clear the status register once every point record has been
+        // read out, so the controller can't overwrite them mid-read.
+        self.write_reg(REG_STATUS, 0)?;
+
+        Ok(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: u16 = 800;
+    const H: u16 = 480;
+
+    #[test]
+    fn deg0_is_identity() {
+        assert_eq!(remap_point(W, H, Rotation::Deg0, false, false, false, 10, 20), (10, 20));
+    }
+
+    #[test]
+    fn deg90_maps_into_the_swapped_frame() {
+        // Top-left raw corner ends up at the bottom-left of the rotated frame.
+        assert_eq!(remap_point(W, H, Rotation::Deg90, false, false, false, 0, 0), (0, W - 1));
+        assert_eq!(remap_point(W, H, Rotation::Deg90, false, false, false, W - 1, 0), (0, 0));
+    }
+
+    #[test]
+    fn deg180_mirrors_both_axes() {
+        assert_eq!(
+            remap_point(W, H, Rotation::Deg180, false, false, false, 0, 0),
+            (W - 1, H - 1)
+        );
+        assert_eq!(
+            remap_point(W, H, Rotation::Deg180, false, false, false, W - 1, H - 1),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn deg270_maps_into_the_swapped_frame() {
+        assert_eq!(remap_point(W, H, Rotation::Deg270, false, false, false, 0, 0), (H - 1, 0));
+        assert_eq!(remap_point(W, H, Rotation::Deg270, false, false, false, 0, H - 1), (0, 0));
+    }
+
+    #[test]
+    fn axis_flags_apply_after_rotation() {
+        // With no rotation, mirror_x flips x within the native width.
+        assert_eq!(
+            remap_point(W, H, Rotation::Deg0, false, true, false, 0, 20),
+            (W - 1, 20)
+        );
+        // swap_xy trades x and y.
+        assert_eq!(
+            remap_point(W, H, Rotation::Deg0, true, false, false, 10, 20),
+            (20, 10)
+        );
+        // With a 90 degree rotation, mirror_x flips x within the rotated
+        // width (H), after the rotation has been applied.
+        assert_eq!(
+            remap_point(W, H, Rotation::Deg90, false, true, false, 0, 0),
+            (H - 1, W - 1)
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Driver for the 800x480 RGB565 parallel panel, wired through the ESP32-S3's
+//! LCD_CAM peripheral via `esp_idf_sys`'s `esp_lcd_rgb_panel` bindings.
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use esp_idf_sys::*;
+
+use lvgl::Color;
+
+// This board's fixed RGB565 parallel wiring. Like the touch controller's
+// I2C/reset/interrupt pins in `main`, these aren't exposed through a config
+// struct since they're a property of the board, not something callers pick.
+const DATA_GPIO_NUMS: [i32; 16] = [8, 3, 46, 9, 1, 5, 6, 7, 15, 16, 4, 45, 48, 47, 14, 13];
+const HSYNC_GPIO_NUM: i32 = 39;
+const VSYNC_GPIO_NUM: i32 = 40;
+const DE_GPIO_NUM: i32 = 41;
+const PCLK_GPIO_NUM: i32 = 42;
+// No dedicated display-enable pin on this board.
+const DISP_GPIO_NUM: i32 = -1;
+
+/// Panel rotation. The `gt911` driver takes this same type so the two can be
+/// kept in sync: whatever orientation the panel draws in, the touch driver must be told
+/// the same thing so raw touch points land in the right place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    /// Whether this rotation swaps the panel's reported horizontal/vertical
+    /// resolution (true for the two "sideways" rotations).
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+}
+
+/// Resolution, pixel clock and flush segment height for the RGB panel. The
+/// data-bus width and pin assignment are fixed by the constants above.
+pub struct PanelConfig {
+    pub hor_res: u32,
+    pub ver_res: u32,
+    pub pclk_hz: u32,
+    /// Height, in rows, of each of the two DMA frame segments `LcdPanel`
+    /// double-buffers flushes through. Lower trades RAM for more frequent,
+    /// smaller DMA kicks; higher trades RAM the other way.
+    pub lines: u32,
+}
+
+impl PanelConfig {
+    /// `const fn` so callers (notably `main`'s `DrawBuffer` sizing) can
+    /// derive their own buffer sizes from `lines` at compile time instead of
+    /// hand-keeping a second constant in sync with it.
+    pub const fn new() -> Self {
+        Self {
+            hor_res: 800,
+            ver_res: 480,
+            pclk_hz: 16_000_000,
+            lines: 12,
+        }
+    }
+}
+
+impl Default for PanelConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Behavioral flags for the RGB panel (currently just the pixel clock edge;
+/// sync polarity lives in `TimingFlagsConfig`).
+pub struct PanelFlagsConfig {
+    pub pclk_active_neg: bool,
+}
+
+impl PanelFlagsConfig {
+    pub fn new() -> Self {
+        Self {
+            pclk_active_neg: false,
+        }
+    }
+}
+
+impl Default for PanelFlagsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pixel color-format flags applied on every flush, for panels that wire
+/// their RGB565 data bus in a different byte order or expect inverted
+/// color levels.
+pub struct ColorConfig {
+    /// Swap the two bytes of each RGB565 pixel before pushing it to the bus.
+    pub swap_rgb565_bytes: bool,
+    /// Invert every pixel's color (XOR with 0xFFFF) before pushing it.
+    pub invert_colors: bool,
+}
+
+impl ColorConfig {
+    pub fn new() -> Self {
+        Self {
+            swap_rgb565_bytes: false,
+            invert_colors: false,
+        }
+    }
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Horizontal/vertical front/back porch and sync pulse widths.
+pub struct TimingsConfig {
+    pub hsync_pulse_width: u32,
+    pub hsync_back_porch: u32,
+    pub hsync_front_porch: u32,
+    pub vsync_pulse_width: u32,
+    pub vsync_back_porch: u32,
+    pub vsync_front_porch: u32,
+}
+
+impl TimingsConfig {
+    pub fn new() -> Self {
+        Self {
+            hsync_pulse_width: 4,
+            hsync_back_porch: 8,
+            hsync_front_porch: 8,
+            vsync_pulse_width: 4,
+            vsync_back_porch: 8,
+            vsync_front_porch: 8,
+        }
+    }
+}
+
+impl Default for TimingsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sync signal polarity flags that accompany `TimingsConfig`.
+pub struct TimingFlagsConfig {
+    pub hsync_idle_low: bool,
+    pub vsync_idle_low: bool,
+    pub de_idle_high: bool,
+}
+
+impl TimingFlagsConfig {
+    pub fn new() -> Self {
+        Self {
+            hsync_idle_low: false,
+            vsync_idle_low: false,
+            de_idle_high: false,
+        }
+    }
+}
+
+impl Default for TimingFlagsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tracks DMA completion independently per segment. The RGB panel peripheral
+/// only ever has one transfer in flight, so `in_flight` (set right before
+/// each `draw_bitmap` call) always names the segment the next completion
+/// event belongs to; `on_color_trans_done` just has to mark that one done.
+struct DmaState {
+    done: [AtomicBool; 2],
+    in_flight: AtomicUsize,
+}
+
+/// Fires when a DMA transfer of one of `LcdPanel`'s segments completes, so
+/// that segment is known free to render into again. `user_ctx` is the
+/// `Arc<DmaState>` pointer passed in at registration.
+unsafe extern "C" fn on_color_trans_done(
+    _panel: esp_lcd_panel_handle_t,
+    _edata: *mut esp_lcd_rgb_panel_event_data_t,
+    user_ctx: *mut c_void,
+) -> bool {
+    let state = &*(user_ctx as *const DmaState);
+    let segment = state.in_flight.load(Ordering::Acquire);
+    state.done[segment].store(true, Ordering::Release);
+    false
+}
+
+/// Owns the `esp_lcd_panel_handle_t` for the RGB panel and converts LVGL's
+/// flush callback into writes against it.
+///
+/// Flushes are double-buffered: `segments` holds two DMA-capable pixel
+/// buffers of `PanelConfig::lines` rows each, and `set_pixels_lvgl_color`
+/// alternates between them so LVGL can render the next segment while the
+/// previous one is still being pushed out over DMA.
+pub struct LcdPanel {
+    handle: esp_lcd_panel_handle_t,
+    native_hor_res: u32,
+    native_ver_res: u32,
+    rotation: Rotation,
+    swap_rgb565_bytes: bool,
+    invert_colors: bool,
+    segments: [Vec<u16>; 2],
+    /// Per-segment DMA completion flags; `set_pixels_lvgl_color` only waits
+    /// on the flag for the segment it's about to overwrite, so the other
+    /// segment's transfer can keep running concurrently.
+    dma_state: Arc<DmaState>,
+    active_segment: usize,
+}
+
+impl LcdPanel {
+    pub fn new(
+        config: &PanelConfig,
+        flags: &PanelFlagsConfig,
+        colors: &ColorConfig,
+        timings: &TimingsConfig,
+        timing_flags: &TimingFlagsConfig,
+    ) -> anyhow::Result<Self> {
+        let mut handle: esp_lcd_panel_handle_t = std::ptr::null_mut();
+
+        let mut timing_flags_bits: esp_lcd_rgb_timing_t__bindgen_ty_1 =
+            unsafe { std::mem::zeroed() };
+        timing_flags_bits.set_hsync_idle_low(timing_flags.hsync_idle_low as u32);
+        timing_flags_bits.set_vsync_idle_low(timing_flags.vsync_idle_low as u32);
+        timing_flags_bits.set_de_idle_high(timing_flags.de_idle_high as u32);
+        timing_flags_bits.set_pclk_active_neg(flags.pclk_active_neg as u32);
+
+        let panel_config = esp_lcd_rgb_panel_config_t {
+            timings: esp_lcd_rgb_timing_t {
+                pclk_hz: config.pclk_hz,
+                h_res: config.hor_res,
+                v_res: config.ver_res,
+                hsync_pulse_width: timings.hsync_pulse_width,
+                hsync_back_porch: timings.hsync_back_porch,
+                hsync_front_porch: timings.hsync_front_porch,
+                vsync_pulse_width: timings.vsync_pulse_width,
+                vsync_back_porch: timings.vsync_back_porch,
+                vsync_front_porch: timings.vsync_front_porch,
+                flags: timing_flags_bits,
+            },
+            data_width: 16,
+            bits_per_pixel: 16,
+            num_fbs: 1,
+            hsync_gpio_num: HSYNC_GPIO_NUM,
+            vsync_gpio_num: VSYNC_GPIO_NUM,
+            de_gpio_num: DE_GPIO_NUM,
+            pclk_gpio_num: PCLK_GPIO_NUM,
+            disp_gpio_num: DISP_GPIO_NUM,
+            data_gpio_nums: DATA_GPIO_NUMS,
+            ..unsafe { std::mem::zeroed() }
+        };
+
+        let segment_pixels = (config.hor_res * config.lines) as usize;
+        let segments = [vec![0u16; segment_pixels], vec![0u16; segment_pixels]];
+        let dma_state = Arc::new(DmaState {
+            done: [AtomicBool::new(true), AtomicBool::new(true)],
+            in_flight: AtomicUsize::new(0),
+        });
+
+        unsafe {
+            esp!(esp_lcd_new_rgb_panel(&panel_config, &mut handle))?;
+            esp!(esp_lcd_panel_reset(handle))?;
+            esp!(esp_lcd_panel_init(handle))?;
+
+            let callbacks = esp_lcd_rgb_panel_event_callbacks_t {
+                on_color_trans_done: Some(on_color_trans_done),
+                ..Default::default()
+            };
+            esp!(esp_lcd_rgb_panel_register_event_callbacks(
+                handle,
+                &callbacks,
+                Arc::as_ptr(&dma_state) as *mut c_void,
+            ))?;
+        }
+
+        Ok(Self {
+            handle,
+            native_hor_res: config.hor_res,
+            native_ver_res: config.ver_res,
+            rotation: Rotation::default(),
+            swap_rgb565_bytes: colors.swap_rgb565_bytes,
+            invert_colors: colors.invert_colors,
+            segments,
+            dma_state,
+            active_segment: 0,
+        })
+    }
+
+    /// Set the rotation the panel draws in. Must match whatever `Rotation`
+    /// the `GT911` touch driver is told, so touch and display agree.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    /// The resolution LVGL should register the display at, i.e. the native
+    /// resolution with width/height swapped for the sideways rotations.
+    pub fn resolution(&self) -> (u32, u32) {
+        if self.rotation.swaps_axes() {
+            (self.native_ver_res, self.native_hor_res)
+        } else {
+            (self.native_hor_res, self.native_ver_res)
+        }
+    }
+
+    /// Map a flush rect in LVGL's (possibly rotated) logical space onto the
+    /// panel's native coordinate space. Inverse of `GT911::remap`'s rotation
+    /// step, so a color written here via `draw_bitmap` lands under the same
+    /// physical point a touch at the same logical coordinate reads back from.
+    fn native_rect(&self, x1: i32, y1: i32, x2: i32, y2: i32) -> (i32, i32, i32, i32) {
+        let (w, h) = (self.native_hor_res as i32, self.native_ver_res as i32);
+        match self.rotation {
+            Rotation::Deg0 => (x1, y1, x2, y2),
+            Rotation::Deg90 => (w - y2, x1, w - y1, x2),
+            Rotation::Deg180 => (w - x2, h - y2, w - x1, h - y1),
+            Rotation::Deg270 => (y1, h - x2, y2, h - x1),
+        }
+    }
+
+    /// Push a run of LVGL colors for the area `(x1, y1)..(x2, y2)` (in LVGL's
+    /// logical, possibly-rotated coordinate space) to the panel, honoring the
+    /// configured rotation, byte-swap and color-invert flags.
+    ///
+    /// Writes land in whichever of the two DMA segments isn't currently
+    /// mid-transfer, so LVGL can keep rendering the next area while this one
+    /// is still being pushed over the bus; we only block if the segment
+    /// we're about to reuse hasn't finished its previous transfer yet.
+    pub fn set_pixels_lvgl_color(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        colors: impl Iterator<Item = Color>,
+    ) -> anyhow::Result<()> {
+        let segment_index = self.active_segment;
+        while !self.dma_state.done[segment_index].load(Ordering::Acquire) {
+            // The previous transfer targeting this segment is still in
+            // flight; give the DMA engine a moment rather than clobbering it.
+            std::hint::spin_loop();
+        }
+
+        let rotation = self.rotation;
+        let swap_rgb565_bytes = self.swap_rgb565_bytes;
+        let invert_colors = self.invert_colors;
+        let convert = |color: Color| {
+            let mut raw = color.to_rgb565();
+            if swap_rgb565_bytes {
+                raw = raw.rotate_left(8);
+            }
+            if invert_colors {
+                raw ^= 0xFFFF;
+            }
+            raw
+        };
+
+        let (native_x1, native_y1, native_x2, native_y2) = self.native_rect(x1, y1, x2, y2);
+        let logical_width = (x2 - x1) as usize;
+        let logical_height = (y2 - y1) as usize;
+
+        let segment = &mut self.segments[segment_index];
+        match rotation {
+            Rotation::Deg0 => {
+                for (slot, color) in segment.iter_mut().zip(colors) {
+                    *slot = convert(color);
+                }
+            }
+            Rotation::Deg180 => {
+                // Flipping both axes at once is the same as reversing the
+                // whole row-major pixel run.
+                let mut pixels: Vec<u16> = colors.map(convert).collect();
+                pixels.reverse();
+                segment[..pixels.len()].copy_from_slice(&pixels);
+            }
+            Rotation::Deg90 | Rotation::Deg270 => {
+                // The sideways rotations also swap width/height, so the run
+                // needs a transpose, not just a reorder.
+                let logical_pixels: Vec<u16> = colors.map(convert).collect();
+                for j in 0..logical_height {
+                    for i in 0..logical_width {
+                        let (ni, nj) = match rotation {
+                            Rotation::Deg90 => (logical_height - 1 - j, i),
+                            Rotation::Deg270 => (j, logical_width - 1 - i),
+                            _ => unreachable!(),
+                        };
+                        segment[nj * logical_height + ni] = logical_pixels[j * logical_width + i];
+                    }
+                }
+            }
+        }
+
+        self.dma_state.done[segment_index].store(false, Ordering::Release);
+        self.dma_state
+            .in_flight
+            .store(segment_index, Ordering::Release);
+        unsafe {
+            esp!(esp_lcd_panel_draw_bitmap(
+                self.handle,
+                native_x1,
+                native_y1,
+                native_x2,
+                native_y2,
+                segment.as_ptr() as *const c_void,
+            ))?;
+        }
+
+        self.active_segment = 1 - self.active_segment;
+
+        Ok(())
+    }
+}
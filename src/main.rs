@@ -1,3 +1,5 @@
+pub mod backlight;
+pub mod gesture;
 pub mod gt911;
 pub mod lcd_panel;
 
@@ -7,12 +9,13 @@ use cstr_core::CString;
 
 use anyhow::Error;
 
-use std::cell::RefCell;
-use std::time::Instant;
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use esp_idf_hal::{
     delay::{Ets, FreeRtos},
-    gpio::PinDriver,
+    gpio::{InterruptType, PinDriver, Pull},
     i2c::{I2cConfig, I2cDriver},
     peripherals::Peripherals,
     units::FromValueType,
@@ -23,6 +26,8 @@ use esp_idf_hal::ledc::{
     {LedcDriver, LedcTimerDriver},
 };
 
+use esp_idf_hal::timer::{TimerConfig as HwTimerConfig, TimerDriver};
+
 use lvgl::style::Style;
 use lvgl::widgets::{Label, Slider};
 use lvgl::{Align, Color, Display, DrawBuffer, Part, TextAlign, Widget};
@@ -33,8 +38,22 @@ use lvgl::input_device::{
     InputDriver,
 };
 
+use crate::backlight::{Backlight, Level};
+use crate::gesture::GestureRecognizer;
 use crate::gt911::GT911;
-use crate::lcd_panel::{LcdPanel, PanelConfig, PanelFlagsConfig, TimingFlagsConfig, TimingsConfig};
+use crate::lcd_panel::{
+    ColorConfig, LcdPanel, PanelConfig, PanelFlagsConfig, Rotation, TimingFlagsConfig,
+    TimingsConfig,
+};
+
+// Both the display and the touch driver need to agree on the panel's
+// orientation so touch coordinates line up with what LVGL draws.
+const PANEL_ROTATION: Rotation = Rotation::Deg0;
+
+/// Set by the GT911 interrupt pin's ISR, cleared when the pointer callback
+/// picks up the new touch data. Avoids polling the controller over I2C
+/// on every `task_handler` tick.
+static TOUCH_READY: AtomicBool = AtomicBool::new(false);
 
 fn main() -> anyhow::Result<(), anyhow::Error> {
     // It is necessary to call this function once. Otherwise some patches to the runtime
@@ -48,7 +67,10 @@ fn main() -> anyhow::Result<(), anyhow::Error> {
 
     const HOR_RES: u32 = 800;
     const VER_RES: u32 = 480;
-    const LINES: u32 = 12; // The number of lines (rows) that will be refreshed
+    // Must match `LcdPanel`'s DMA segment height, so the `DrawBuffer` LVGL
+    // flushes from is never bigger than the segments `set_pixels_lvgl_color`
+    // copies into; derive it from `PanelConfig` rather than hard-coding it.
+    const LINES: u32 = PanelConfig::new().lines;
 
     let peripherals = Peripherals::take()?;
 
@@ -65,10 +87,20 @@ fn main() -> anyhow::Result<(), anyhow::Error> {
     let i2c = I2cDriver::new(i2c, sda, scl, &config)?;
     let rst = PinDriver::output(pins.gpio38)?; // reset pin on GT911
 
+    // GT911 INT line: pulled low by the controller when new touch data is
+    // ready, rather than polling it on a fixed interval.
+    let mut touch_int = PinDriver::input(pins.gpio21)?;
+    touch_int.set_pull(Pull::Up)?;
+    touch_int.set_interrupt_type(InterruptType::NegEdge)?;
+    unsafe {
+        touch_int.subscribe(|| TOUCH_READY.store(true, Ordering::Relaxed))?;
+    }
+    touch_int.enable_interrupt()?;
+
     //============================================================================================================
     //               Create the LedcDriver to drive the backlight on the Lcd Panel
     //============================================================================================================
-    let mut channel = LedcDriver::new(
+    let channel = LedcDriver::new(
         peripherals.ledc.channel0,
         LedcTimerDriver::new(
             peripherals.ledc.timer0,
@@ -77,7 +109,8 @@ fn main() -> anyhow::Result<(), anyhow::Error> {
         .unwrap(),
         pins.gpio2,
     )?;
-    channel.set_duty(channel.get_max_duty() / 2)?;
+    let backlight = RefCell::new(Backlight::new(channel));
+    backlight.borrow_mut().set_level(Level::Medium)?;
     info!("============= Backlight turned on =============");
 
     // Initialize lvgl
@@ -89,13 +122,16 @@ fn main() -> anyhow::Result<(), anyhow::Error> {
     let mut lcd_panel = LcdPanel::new(
         &PanelConfig::new(),
         &PanelFlagsConfig::new(),
+        &ColorConfig::new(),
         &TimingsConfig::new(),
         &TimingFlagsConfig::new(),
     )?;
+    lcd_panel.set_rotation(PANEL_ROTATION);
+    let (hor_res, ver_res) = lcd_panel.resolution();
 
     info!("=============  Registering Display ====================");
     let buffer = DrawBuffer::<{ (HOR_RES * LINES) as usize }>::default();
-    let display = Display::register(buffer, HOR_RES, VER_RES, |refresh| {
+    let display = Display::register(buffer, hor_res, ver_res, |refresh| {
         lcd_panel
             .set_pixels_lvgl_color(
                 refresh.area.x1.into(),
@@ -111,15 +147,36 @@ fn main() -> anyhow::Result<(), anyhow::Error> {
     //======================================================================================================
     //                          Create the driver for the Touchscreen
     //======================================================================================================
-    let gt911_touchscreen = RefCell::new(GT911::new(i2c, rst, Ets));
-    gt911_touchscreen.borrow_mut().reset()?;
-
-    // The read_touchscreen_cb is used by Lvgl to detect touchscreen presses and releases
+    let mut gt911_touchscreen = GT911::new(i2c, rst, Ets, HOR_RES as u16, VER_RES as u16);
+    gt911_touchscreen.set_rotation(PANEL_ROTATION);
+    // This board's touch controller axes line up with the panel's, so no
+    // extra swap/mirror is needed; boards that wire it differently should
+    // set these from gt911_touchscreen.set_axis_flags(..).
+    gt911_touchscreen.reset()?;
+    let gt911_touchscreen = RefCell::new(gt911_touchscreen);
+
+    let gesture_recognizer = RefCell::new(GestureRecognizer::new());
+
+    // Lvgl polls this callback every task_handler() tick, but it only goes
+    // out to the GT911 over I2C when the INT line has signaled new data;
+    // otherwise it just replays the last known touch state. The contacts
+    // read are also fed to the gesture recognizer, so swipes/taps/long-presses
+    // are seen, not just the LVGL pointer.
+    let last_touch = Cell::new(None);
     let read_touchscreen_cb = || {
-        let touch = gt911_touchscreen.borrow_mut().read_touch().unwrap();
+        if TOUCH_READY.swap(false, Ordering::Relaxed) {
+            let points = gt911_touchscreen.borrow_mut().read_touches().unwrap();
 
-        match touch {
-            Some(tp) => PointerInputData::Touch(Point::new(tp.x as i32, tp.y as i32))
+            if let Some(gesture) = gesture_recognizer.borrow_mut().update(&points) {
+                info!("Gesture detected: {:?}", gesture);
+            }
+
+            last_touch.set(points.first().map(|p| (p.x, p.y)));
+            touch_int.enable_interrupt().unwrap();
+        }
+
+        match last_touch.get() {
+            Some((x, y)) => PointerInputData::Touch(Point::new(x as i32, y as i32))
                 .pressed()
                 .once(),
             None => PointerInputData::Touch(Point::new(0, 0)).released().once(),
@@ -168,18 +225,31 @@ fn main() -> anyhow::Result<(), anyhow::Error> {
             if let lvgl::Event::ValueChanged = event {
                 let value = slider.get_value();
                 let _ = percent_label.set_text(&CString::new(format!("%{}", value)).unwrap());
+                let _ = backlight.borrow_mut().set_percent(value as u32);
             }
         })
         .map_err(Error::msg)?;
 
-    loop {
-        let start = Instant::now();
+    //=======================================================================================================
+    //                      Monotonic tick source, fed from a dedicated hardware timer
+    //=======================================================================================================
+    let mut tick_timer = TimerDriver::new(peripherals.timer00, &HwTimerConfig::new())?;
+    // TimerDriver::new() only configures the peripheral; the counter doesn't
+    // run until it's explicitly enabled, so start it before reading it below.
+    tick_timer.enable(true)?;
+    let tick_hz = tick_timer.tick_hz();
+    let mut last_ticks = tick_timer.counter()?;
 
+    loop {
         lvgl::task_handler();
 
-        // Keep the loop delay short so Lvgl can respond quickly to touchscreen presses and releases
-        FreeRtos::delay_ms(20);
+        // Touch is now interrupt-driven, so this delay is just an idle sleep
+        // between LVGL redraw/animation ticks, not a touch poll interval.
+        FreeRtos::delay_ms(5);
 
-        lvgl::tick_inc(Instant::now().duration_since(start));
+        let ticks = tick_timer.counter()?;
+        let elapsed_ms = ticks.wrapping_sub(last_ticks) * 1000 / tick_hz;
+        last_ticks = ticks;
+        lvgl::tick_inc(Duration::from_millis(elapsed_ms));
     }
 }